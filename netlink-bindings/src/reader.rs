@@ -0,0 +1,115 @@
+#![cfg(feature = "std")]
+
+//! Incremental TLV parsing directly off a [`Read`] source.
+//!
+//! [`chop_header`](crate::utils::chop_header) and [`Iterable`](crate::utils::Iterable)
+//! require the whole netlink message to already be sitting in a `&[u8]`.
+//! [`StreamReader`] instead pulls bytes from a [`Read`] on demand, buffering
+//! only what hasn't been consumed yet, so a caller streaming a multipart
+//! `NLMSG_DONE` dump across several socket reads doesn't have to buffer the
+//! whole thing up front.
+
+use std::io::{self, Read};
+
+use crate::utils::{nla_align_up, nla_type, ErrorReason, Header, NLA_F_NESTED};
+
+/// Pulls [`Header`] + payload pairs out of a [`Read`] source, one TLV at a
+/// time, the way [`chop_header`](crate::utils::chop_header) pulls them out of
+/// a slice.
+pub struct StreamReader<R> {
+    inner: R,
+    buf: Vec<u8>,
+    /// Bytes at the front of `buf` that belong to an already-yielded record
+    /// (including its alignment padding) and can be dropped on the next call.
+    consumed: usize,
+}
+
+impl<R: Read> StreamReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            buf: Vec::new(),
+            consumed: 0,
+        }
+    }
+
+    /// Reads the next TLV header and payload.
+    ///
+    /// * `Ok(None)` means the stream ended cleanly between records.
+    /// * `Ok(Some((header, payload)))` is a fully parsed record.
+    /// * `Err(ErrorReason::UnexpectedEof)` means the source ended in the
+    ///   middle of a TLV whose length prefix promised more bytes than
+    ///   arrived; calling this again once more data is available to `inner`
+    ///   resumes from the same partial record.
+    /// * `Err(ErrorReason::ParsingError)` means the length prefix is shorter
+    ///   than the 4-byte TLV header itself.
+    /// * An outer `io::Error` is a genuine I/O error from the underlying
+    ///   source.
+    pub fn next_record(&mut self) -> io::Result<Result<Option<(Header, Vec<u8>)>, ErrorReason>> {
+        if self.consumed > 0 {
+            self.buf.drain(..self.consumed);
+            self.consumed = 0;
+        }
+
+        match self.fill(4)? {
+            Fill::Eof if self.buf.is_empty() => return Ok(Ok(None)),
+            Fill::Eof => return Ok(Err(ErrorReason::UnexpectedEof)),
+            Fill::Full => {}
+        }
+
+        let len = u16::from_ne_bytes([self.buf[0], self.buf[1]]) as usize;
+        let r#type = u16::from_ne_bytes([self.buf[2], self.buf[3]]);
+
+        if len < 4 {
+            return Ok(Err(ErrorReason::ParsingError));
+        }
+
+        if let Fill::Eof = self.fill(len)? {
+            return Ok(Err(ErrorReason::UnexpectedEof));
+        }
+
+        let payload = self.buf[4..len].to_vec();
+
+        // Try to read through the end of the alignment padding too, so a
+        // short `read()` landing exactly on the payload boundary doesn't
+        // leave unread padding bytes in the stream for the next call to
+        // misparse as the start of a header. Unlike `len` itself, the
+        // padding isn't guaranteed to ever be transmitted (mirrors
+        // `chop_header`'s `.min(buf.len())` clamp), so an EOF here just means
+        // there's no padding to consume, not a truncated record.
+        let aligned_len = nla_align_up(len);
+        self.fill(aligned_len)?;
+        self.consumed = aligned_len.min(self.buf.len());
+
+        Ok(Ok(Some((
+            Header {
+                r#type: nla_type(r#type),
+                is_nested: r#type & NLA_F_NESTED != 0,
+            },
+            payload,
+        ))))
+    }
+
+    /// Reads from `inner` until `buf` holds at least `want` bytes, the way
+    /// [`Read::read_exact`] would, except a clean EOF is reported instead of
+    /// turned into an error so the caller can tell "nothing pending" apart
+    /// from "a record was cut short".
+    fn fill(&mut self, want: usize) -> io::Result<Fill> {
+        let mut scratch = [0u8; 4096];
+        while self.buf.len() < want {
+            let read = self.inner.read(&mut scratch)?;
+            if read == 0 {
+                return Ok(Fill::Eof);
+            }
+            self.buf.extend_from_slice(&scratch[..read]);
+        }
+        Ok(Fill::Full)
+    }
+}
+
+enum Fill {
+    /// `buf` now holds at least the requested number of bytes.
+    Full,
+    /// `inner` returned EOF before `buf` reached the requested length.
+    Eof,
+}
@@ -0,0 +1,554 @@
+#![cfg(feature = "std")]
+
+//! Transport clients that actually talk to the kernel.
+//!
+//! Everything else in this crate only builds request bytes
+//! ([`push_header`](crate::utils::push_header),
+//! [`finalize_nested_header`](crate::utils::finalize_nested_header), the
+//! [`Rec`](crate::utils::Rec) trait) or parses response bytes
+//! ([`Iterable`](crate::utils::Iterable)). [`NetlinkClient`] and
+//! [`AsyncNetlinkClient`] are the missing piece: they open an `AF_NETLINK`
+//! socket, wrap a payload in the kernel's `nlmsghdr`, and hand typed
+//! attribute iterators back to the caller.
+//!
+//! The split mirrors a sync/async RPC client pair: [`NetlinkClient::send`]
+//! blocks until the whole multipart dump has arrived, any `NLMSG_ERROR` with
+//! a non-zero errno has been turned into a [`TransportError::Kernel`], and
+//! the reply's sequence
+//! number has been checked against the request -- like a blockchain RPC
+//! client's sync half waiting for a transaction to confirm.
+//! [`AsyncNetlinkClient::send`] fires the request and immediately hands back
+//! a [`Stream`] of responses as they trickle in, without blocking the caller
+//! while the dump is still in flight. Both sides keep sequence-number
+//! matching and dump continuation internal, so callers just get back typed
+//! attribute iterators.
+
+use std::io;
+
+use crate::utils::Iterable;
+
+pub const NLMSG_NOOP: u16 = 1;
+pub const NLMSG_ERROR: u16 = 2;
+pub const NLMSG_DONE: u16 = 3;
+
+pub const NLM_F_MULTI: u16 = 0x2;
+pub const NLM_F_DUMP: u16 = 0x100 | 0x200; // NLM_F_ROOT | NLM_F_MATCH
+
+/// The `nlmsghdr` the kernel prefixes every reply with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NlMsgHeader {
+    pub len: u32,
+    pub r#type: u16,
+    pub flags: u16,
+    pub seq: u32,
+    pub pid: u32,
+}
+
+pub const NLMSGHDR_LEN: usize = 16;
+
+/// Parses one `nlmsghdr` + payload out of `buf`, the way
+/// [`chop_header`](crate::utils::chop_header) parses one attribute TLV.
+/// Returns `None` if `buf` doesn't hold a complete message.
+pub fn chop_nlmsg(buf: &[u8]) -> Option<(NlMsgHeader, &[u8], &[u8])> {
+    if buf.len() < NLMSGHDR_LEN {
+        return None;
+    }
+
+    let len = u32::from_ne_bytes(buf[0..4].try_into().unwrap());
+    if (len as usize) < NLMSGHDR_LEN || buf.len() < len as usize {
+        return None;
+    }
+
+    let header = NlMsgHeader {
+        len,
+        r#type: u16::from_ne_bytes(buf[4..6].try_into().unwrap()),
+        flags: u16::from_ne_bytes(buf[6..8].try_into().unwrap()),
+        seq: u32::from_ne_bytes(buf[8..12].try_into().unwrap()),
+        pid: u32::from_ne_bytes(buf[12..16].try_into().unwrap()),
+    };
+
+    let payload = &buf[NLMSGHDR_LEN..len as usize];
+    // `nlmsghdr`s aren't guaranteed to be 4-byte aligned, same as the NLA
+    // case `chop_header` handles; round up to the next message and clamp to
+    // what's actually buffered the same way `chop_header` does.
+    let next = crate::utils::nla_align_up(len as usize).min(buf.len());
+    let rest = &buf[next..];
+    Some((header, payload, rest))
+}
+
+/// Error surfaced by [`NetlinkClient`]/[`AsyncNetlinkClient`] in addition to
+/// plain socket I/O failures.
+#[derive(Debug)]
+pub enum TransportError {
+    /// The socket itself failed.
+    Io(io::Error),
+    /// The kernel answered with `NLMSG_ERROR` carrying this non-zero errno.
+    /// A zero errno (a plain ack) ends the reply normally instead.
+    Kernel(i32),
+    /// A reply's sequence number didn't match the request that's waiting for
+    /// it.
+    UnexpectedSeq { expected: u32, got: u32 },
+}
+
+impl From<io::Error> for TransportError {
+    fn from(err: io::Error) -> Self {
+        TransportError::Io(err)
+    }
+}
+
+impl std::fmt::Display for TransportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransportError::Io(err) => write!(f, "netlink socket error: {err}"),
+            TransportError::Kernel(errno) => write!(f, "kernel returned errno {errno}"),
+            TransportError::UnexpectedSeq { expected, got } => {
+                write!(f, "expected reply to seq {expected}, got seq {got}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TransportError {}
+
+/// The fully-received, still-unparsed messages of one request's reply (a
+/// single message for a plain request, every fragment before `NLMSG_DONE`
+/// for a dump; the `NLMSG_DONE` message itself is not kept).
+pub struct Dump {
+    messages: Vec<Vec<u8>>,
+}
+
+impl Dump {
+    /// Parses each message payload as `AttrSet`, mirroring how
+    /// [`Iterable::new`](crate::utils::Iterable::new) parses a single
+    /// message today.
+    pub fn iter<AttrSet>(&self) -> impl Iterator<Item = Iterable<'_, AttrSet>> {
+        self.messages.iter().map(|msg| Iterable::new(msg))
+    }
+
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+}
+
+/// Sends finalized request buffers to the kernel over an `AF_NETLINK`
+/// socket and blocks until the full reply has arrived.
+pub trait NetlinkClient {
+    /// Wraps `payload` (already built with
+    /// [`push_header`](crate::utils::push_header) /
+    /// [`finalize_nested_header`](crate::utils::finalize_nested_header)) in
+    /// an `nlmsghdr` of the given `msg_type`/`flags`, sends it, and blocks
+    /// until the matching reply -- or, if `flags` requests a dump, every
+    /// fragment through `NLMSG_DONE` -- has arrived. `NLMSG_ERROR` replies
+    /// with a non-zero errno are surfaced as [`TransportError::Kernel`]
+    /// instead of being handed back as data.
+    fn send(&mut self, msg_type: u16, flags: u16, payload: &[u8]) -> Result<Dump, TransportError>;
+}
+
+#[cfg(target_os = "linux")]
+mod linux_socket {
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+
+    use super::*;
+
+    /// A [`NetlinkClient`] backed by a real `AF_NETLINK` socket.
+    pub struct NetlinkSocket {
+        fd: OwnedFd,
+        pid: u32,
+        seq: u32,
+    }
+
+    impl NetlinkSocket {
+        /// Opens an `AF_NETLINK` socket for the given protocol (e.g.
+        /// `NETLINK_ROUTE`) and binds it, letting the kernel assign a port
+        /// id.
+        pub fn open(protocol: libc::c_int) -> io::Result<Self> {
+            // SAFETY: `socket(2)` with these arguments either returns a valid
+            // owned fd or -1; we check for -1 below.
+            let raw: RawFd = unsafe { libc::socket(libc::AF_NETLINK, libc::SOCK_RAW, protocol) };
+            if raw < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            // SAFETY: `raw` was just returned by `socket(2)` above and isn't
+            // owned anywhere else yet.
+            let fd = unsafe { OwnedFd::from_raw_fd(raw) };
+
+            let mut addr: libc::sockaddr_nl = unsafe { std::mem::zeroed() };
+            addr.nl_family = libc::AF_NETLINK as u16;
+
+            // SAFETY: `addr` is a valid `sockaddr_nl` of the size passed in.
+            let rc = unsafe {
+                libc::bind(
+                    fd.as_raw_fd(),
+                    std::ptr::addr_of!(addr).cast(),
+                    std::mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+                )
+            };
+            if rc < 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            let mut bound: libc::sockaddr_nl = unsafe { std::mem::zeroed() };
+            let mut len = std::mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t;
+            // SAFETY: `bound`/`len` describe a writable buffer of that size.
+            let rc = unsafe {
+                libc::getsockname(fd.as_raw_fd(), std::ptr::addr_of_mut!(bound).cast(), &mut len)
+            };
+            if rc < 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            Ok(Self {
+                fd,
+                pid: bound.nl_pid,
+                seq: 0,
+            })
+        }
+
+        fn write_all(&self, buf: &[u8]) -> io::Result<()> {
+            // SAFETY: `buf` is a valid slice for `buf.len()` bytes.
+            let n = unsafe {
+                libc::send(
+                    self.fd.as_raw_fd(),
+                    buf.as_ptr().cast(),
+                    buf.len(),
+                    0,
+                )
+            };
+            if n < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        }
+
+        fn recv(&self, buf: &mut [u8]) -> io::Result<usize> {
+            // SAFETY: `buf` is a valid, writable slice for `buf.len()` bytes.
+            let n = unsafe {
+                libc::recv(self.fd.as_raw_fd(), buf.as_mut_ptr().cast(), buf.len(), 0)
+            };
+            if n < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(n as usize)
+        }
+    }
+
+    impl NetlinkClient for NetlinkSocket {
+        fn send(
+            &mut self,
+            msg_type: u16,
+            flags: u16,
+            payload: &[u8],
+        ) -> Result<Dump, TransportError> {
+            self.seq = self.seq.wrapping_add(1);
+            let seq = self.seq;
+
+            let mut request = Vec::with_capacity(NLMSGHDR_LEN + payload.len());
+            request.extend(((NLMSGHDR_LEN + payload.len()) as u32).to_ne_bytes());
+            request.extend(msg_type.to_ne_bytes());
+            request.extend(flags.to_ne_bytes());
+            request.extend(seq.to_ne_bytes());
+            request.extend(self.pid.to_ne_bytes());
+            request.extend_from_slice(payload);
+
+            self.write_all(&request)?;
+
+            let expects_multipart = flags & (NLM_F_MULTI | NLM_F_DUMP) != 0;
+            let mut messages = Vec::new();
+            let mut recv_buf = [0u8; 32 * 1024];
+
+            loop {
+                let n = self.recv(&mut recv_buf)?;
+                let mut rest = &recv_buf[..n];
+
+                while let Some((header, body, tail)) = chop_nlmsg(rest) {
+                    rest = tail;
+
+                    if header.seq != seq {
+                        return Err(TransportError::UnexpectedSeq {
+                            expected: seq,
+                            got: header.seq,
+                        });
+                    }
+
+                    match header.r#type {
+                        NLMSG_ERROR => {
+                            let errno = i32::from_ne_bytes(body[0..4].try_into().unwrap());
+                            if errno != 0 {
+                                return Err(TransportError::Kernel(errno));
+                            }
+                            return Ok(Dump { messages });
+                        }
+                        NLMSG_DONE => return Ok(Dump { messages }),
+                        NLMSG_NOOP => {}
+                        _ => messages.push(body.to_vec()),
+                    }
+                }
+
+                if !expects_multipart {
+                    break;
+                }
+            }
+
+            Ok(Dump { messages })
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub use linux_socket::NetlinkSocket;
+
+#[cfg(feature = "async")]
+mod r#async {
+    use core::future::Future;
+    use core::pin::Pin;
+    use core::task::{Context, Poll};
+
+    use futures_core::Stream;
+
+    use super::*;
+
+    /// Async counterpart of [`NetlinkClient`]. Instead of blocking until the
+    /// whole dump has arrived, `send` returns as soon as the request has
+    /// been written and hands back a [`Stream`] the caller polls to get
+    /// messages as they come in.
+    pub trait AsyncNetlinkClient {
+        type Stream<'a>: Stream<Item = Result<Vec<u8>, TransportError>> + 'a
+        where
+            Self: 'a;
+
+        /// Wraps `payload` in an `nlmsghdr` of the given `msg_type`/`flags`
+        /// and sends it, the same as [`NetlinkClient::send`], but returns a
+        /// stream of raw message payloads instead of waiting for the dump to
+        /// finish. Sequence-number checking and recognizing `NLMSG_DONE` /
+        /// `NLMSG_ERROR` happen inside the stream as it's polled.
+        fn send<'a>(
+            &'a mut self,
+            msg_type: u16,
+            flags: u16,
+            payload: &'a [u8],
+        ) -> impl Future<Output = Result<Self::Stream<'a>, TransportError>> + 'a;
+    }
+
+    #[cfg(target_os = "linux")]
+    mod linux_socket {
+        use std::collections::VecDeque;
+        use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+
+        use tokio::io::unix::AsyncFd;
+
+        use super::*;
+
+        /// An [`AsyncNetlinkClient`] backed by a real `AF_NETLINK` socket
+        /// driven through Tokio's reactor.
+        pub struct AsyncNetlinkSocket {
+            fd: AsyncFd<OwnedFd>,
+            pid: u32,
+            seq: u32,
+        }
+
+        impl AsyncNetlinkSocket {
+            pub fn open(protocol: libc::c_int) -> io::Result<Self> {
+                // SAFETY: same preconditions as the sync `NetlinkSocket::open`.
+                let raw: RawFd =
+                    unsafe { libc::socket(libc::AF_NETLINK, libc::SOCK_RAW | libc::SOCK_NONBLOCK, protocol) };
+                if raw < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                // SAFETY: `raw` was just returned by `socket(2)` above.
+                let fd = unsafe { OwnedFd::from_raw_fd(raw) };
+
+                let mut addr: libc::sockaddr_nl = unsafe { std::mem::zeroed() };
+                addr.nl_family = libc::AF_NETLINK as u16;
+                // SAFETY: `addr` is a valid `sockaddr_nl` of the size passed in.
+                let rc = unsafe {
+                    libc::bind(
+                        fd.as_raw_fd(),
+                        std::ptr::addr_of!(addr).cast(),
+                        std::mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+                    )
+                };
+                if rc < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+
+                let mut bound: libc::sockaddr_nl = unsafe { std::mem::zeroed() };
+                let mut len = std::mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t;
+                // SAFETY: `bound`/`len` describe a writable buffer of that size.
+                let rc = unsafe {
+                    libc::getsockname(fd.as_raw_fd(), std::ptr::addr_of_mut!(bound).cast(), &mut len)
+                };
+                if rc < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+
+                Ok(Self {
+                    fd: AsyncFd::new(fd)?,
+                    pid: bound.nl_pid,
+                    seq: 0,
+                })
+            }
+        }
+
+        /// [`Stream`] returned by [`AsyncNetlinkSocket::send`]; yields one
+        /// message payload per poll until `NLMSG_DONE`/`NLMSG_ERROR` ends the
+        /// reply.
+        pub struct RecvStream<'a> {
+            fd: &'a AsyncFd<OwnedFd>,
+            seq: u32,
+            expects_multipart: bool,
+            pending: VecDeque<Vec<u8>>,
+            done: bool,
+        }
+
+        impl Stream for RecvStream<'_> {
+            type Item = Result<Vec<u8>, TransportError>;
+
+            fn poll_next(
+                mut self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+            ) -> Poll<Option<Self::Item>> {
+                if let Some(msg) = self.pending.pop_front() {
+                    return Poll::Ready(Some(Ok(msg)));
+                }
+                if self.done {
+                    return Poll::Ready(None);
+                }
+
+                loop {
+                    let mut guard = match self.fd.poll_read_ready(cx) {
+                        Poll::Ready(Ok(guard)) => guard,
+                        Poll::Ready(Err(err)) => {
+                            return Poll::Ready(Some(Err(TransportError::Io(err))))
+                        }
+                        Poll::Pending => return Poll::Pending,
+                    };
+
+                    let mut buf = [0u8; 32 * 1024];
+                    // SAFETY: `buf` is a valid, writable slice for `buf.len()` bytes.
+                    let n = unsafe {
+                        libc::recv(
+                            guard.get_inner().as_raw_fd(),
+                            buf.as_mut_ptr().cast(),
+                            buf.len(),
+                            0,
+                        )
+                    };
+                    if n < 0 {
+                        let err = io::Error::last_os_error();
+                        if err.kind() == io::ErrorKind::WouldBlock {
+                            guard.clear_ready();
+                            continue;
+                        }
+                        return Poll::Ready(Some(Err(TransportError::Io(err))));
+                    }
+
+                    let mut rest = &buf[..n as usize];
+                    let mut first = None;
+
+                    while let Some((header, body, tail)) = chop_nlmsg(rest) {
+                        rest = tail;
+
+                        if header.seq != self.seq {
+                            return Poll::Ready(Some(Err(TransportError::UnexpectedSeq {
+                                expected: self.seq,
+                                got: header.seq,
+                            })));
+                        }
+
+                        match header.r#type {
+                            NLMSG_ERROR => {
+                                let errno = i32::from_ne_bytes(body[0..4].try_into().unwrap());
+                                self.done = true;
+                                if errno != 0 {
+                                    return Poll::Ready(Some(Err(TransportError::Kernel(errno))));
+                                }
+                                return Poll::Ready(first.take().map(Ok));
+                            }
+                            NLMSG_DONE => {
+                                self.done = true;
+                                return Poll::Ready(first.take().map(Ok));
+                            }
+                            NLMSG_NOOP => {}
+                            _ if first.is_none() => first = Some(body.to_vec()),
+                            _ => self.pending.push_back(body.to_vec()),
+                        }
+                    }
+
+                    if !self.expects_multipart {
+                        self.done = true;
+                    }
+                    // A dump datagram that carried no data message (e.g. only
+                    // `NLMSG_NOOP`) doesn't end the reply; keep reading.
+                    if first.is_none() && !self.done {
+                        continue;
+                    }
+                    return Poll::Ready(first.map(Ok));
+                }
+            }
+        }
+
+        impl AsyncNetlinkClient for AsyncNetlinkSocket {
+            type Stream<'a> = RecvStream<'a>;
+
+            async fn send<'a>(
+                &'a mut self,
+                msg_type: u16,
+                flags: u16,
+                payload: &'a [u8],
+            ) -> Result<RecvStream<'a>, TransportError> {
+                self.seq = self.seq.wrapping_add(1);
+                let seq = self.seq;
+
+                let mut request = Vec::with_capacity(NLMSGHDR_LEN + payload.len());
+                request.extend(((NLMSGHDR_LEN + payload.len()) as u32).to_ne_bytes());
+                request.extend(msg_type.to_ne_bytes());
+                request.extend(flags.to_ne_bytes());
+                request.extend(seq.to_ne_bytes());
+                request.extend(self.pid.to_ne_bytes());
+                request.extend_from_slice(payload);
+
+                loop {
+                    let mut guard = self.fd.writable().await?;
+                    // SAFETY: `request` is a valid slice for `request.len()` bytes.
+                    let n = unsafe {
+                        libc::send(
+                            guard.get_inner().as_raw_fd(),
+                            request.as_ptr().cast(),
+                            request.len(),
+                            0,
+                        )
+                    };
+                    if n < 0 {
+                        let err = io::Error::last_os_error();
+                        if err.kind() == io::ErrorKind::WouldBlock {
+                            guard.clear_ready();
+                            continue;
+                        }
+                        return Err(TransportError::Io(err));
+                    }
+                    break;
+                }
+
+                Ok(RecvStream {
+                    fd: &self.fd,
+                    seq,
+                    expects_multipart: flags & (NLM_F_MULTI | NLM_F_DUMP) != 0,
+                    pending: VecDeque::new(),
+                    done: false,
+                })
+            }
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    pub use linux_socket::{AsyncNetlinkSocket, RecvStream};
+}
+
+#[cfg(feature = "async")]
+pub use r#async::AsyncNetlinkClient;
+#[cfg(all(feature = "async", target_os = "linux"))]
+pub use r#async::{AsyncNetlinkSocket, RecvStream};
@@ -1,8 +1,12 @@
-use std::{fmt, marker::PhantomData};
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::{fmt, marker::PhantomData};
 
 pub use crate::primitives::*;
-pub use std::{ffi::CStr, fmt::Debug, iter::Iterator};
+pub use core::{ffi::CStr, fmt::Debug, iter::Iterator};
 
+#[cfg(feature = "std")]
 pub fn dump_hex(buf: &[u8]) {
     let mut len = 0;
     for chunk in buf.chunks(16) {
@@ -20,6 +24,7 @@ pub fn dump_hex(buf: &[u8]) {
     }
 }
 
+#[cfg(feature = "std")]
 pub fn dump_assert_eq(left: &[u8], right: &[u8]) {
     if left.len() != right.len() {
         dump_hex(left);
@@ -64,6 +69,9 @@ pub enum ErrorReason {
     ParsingError,
     /// Found attribute of type not mentioned in the specification
     UnknownAttr,
+    /// A streaming source ended in the middle of a TLV whose length prefix
+    /// promised more bytes than actually arrived
+    UnexpectedEof,
 }
 
 #[derive(Clone, PartialEq, Eq)]
@@ -74,7 +82,7 @@ pub struct ErrorContext {
     pub reason: ErrorReason,
 }
 
-impl std::error::Error for ErrorContext {}
+impl core::error::Error for ErrorContext {}
 
 impl fmt::Debug for ErrorContext {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -141,7 +149,7 @@ pub const fn nla_align_up(len: usize) -> usize {
 
 pub fn align(buf: &mut Vec<u8>) {
     let len = buf.len();
-    buf.extend(std::iter::repeat_n(0u8, nla_align_up(len) - len));
+    buf.extend(core::iter::repeat_n(0u8, nla_align_up(len) - len));
 }
 
 /// Returns header offset